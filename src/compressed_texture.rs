@@ -0,0 +1,204 @@
+use anyhow::{anyhow, bail, Result};
+
+/// A GPU block-compressed texture format this crate can upload directly from a KTX2/DDS
+/// container, without re-encoding through the CPU-side image decoder.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompressedFormat {
+    Bc1,
+    Bc3,
+    Bc7,
+    Etc2Rgba8,
+    Astc4x4,
+}
+
+impl CompressedFormat {
+    /// The `wgpu::Features` flag that must be enabled on the device to sample this format.
+    pub(crate) fn required_feature(self) -> wgpu::Features {
+        match self {
+            Self::Bc1 | Self::Bc3 | Self::Bc7 => wgpu::Features::TEXTURE_COMPRESSION_BC,
+            Self::Etc2Rgba8 => wgpu::Features::TEXTURE_COMPRESSION_ETC2,
+            Self::Astc4x4 => wgpu::Features::TEXTURE_COMPRESSION_ASTC,
+        }
+    }
+
+    /// The corresponding sRGB `wgpu::TextureFormat`, matching the sRGB encoding
+    /// `Texture::from_image` already uses for normally-decoded textures.
+    pub(crate) fn texture_format(self) -> wgpu::TextureFormat {
+        match self {
+            Self::Bc1 => wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+            Self::Bc3 => wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+            Self::Bc7 => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+            Self::Etc2Rgba8 => wgpu::TextureFormat::Etc2Rgba8UnormSrgb,
+            Self::Astc4x4 => wgpu::TextureFormat::Astc {
+                block: wgpu::AstcBlock::B4x4,
+                channel: wgpu::AstcChannel::UnormSrgb,
+            },
+        }
+    }
+
+    /// Bytes per 4x4 texel block.
+    pub(crate) fn block_size(self) -> u32 {
+        match self {
+            Self::Bc1 => 8,
+            Self::Bc3 | Self::Bc7 | Self::Etc2Rgba8 | Self::Astc4x4 => 16,
+        }
+    }
+}
+
+/// A decoded-but-still-block-compressed texture: dimensions plus one tightly packed byte
+/// buffer per mip level (largest first), ready to upload straight to the GPU.
+pub struct CompressedImage {
+    pub format: CompressedFormat,
+    pub width: u32,
+    pub height: u32,
+    pub mips: Vec<Vec<u8>>,
+}
+
+const KTX2_MAGIC: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+/// Sniffs `bytes` for a KTX2 or DDS container and, if found, decodes its header and mip
+/// chain. Returns `None` when neither magic matches, so the caller can fall back to normal
+/// image decoding instead.
+pub(crate) fn parse(bytes: &[u8]) -> Option<Result<CompressedImage>> {
+    if bytes.starts_with(&KTX2_MAGIC) {
+        Some(parse_ktx2(bytes))
+    } else if bytes.starts_with(b"DDS ") {
+        Some(parse_dds(bytes))
+    } else {
+        None
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let slice = bytes
+        .get(offset..offset + 4)
+        .ok_or_else(|| anyhow!("truncated compressed texture header"))?;
+    Ok(u32::from_le_bytes(slice.try_into().unwrap()))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64> {
+    let slice = bytes
+        .get(offset..offset + 8)
+        .ok_or_else(|| anyhow!("truncated compressed texture header"))?;
+    Ok(u64::from_le_bytes(slice.try_into().unwrap()))
+}
+
+/// Slices `level_count` (at least 1) mip levels of `format`-sized blocks out of `data`,
+/// largest first, assuming they're packed back-to-back with no padding between levels.
+fn mip_chain(
+    format: CompressedFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+    level_count: u32,
+) -> Result<Vec<Vec<u8>>> {
+    let mut mips = Vec::new();
+    let mut offset = 0usize;
+
+    for level in 0..level_count.max(1) {
+        let mip_width = (width >> level).max(1);
+        let mip_height = (height >> level).max(1);
+        let blocks_wide = (mip_width + 3) / 4;
+        let blocks_high = (mip_height + 3) / 4;
+        let size = (blocks_wide * blocks_high * format.block_size()) as usize;
+
+        let slice = data
+            .get(offset..offset + size)
+            .ok_or_else(|| anyhow!("truncated mip level {level}"))?;
+        mips.push(slice.to_vec());
+        offset += size;
+    }
+
+    Ok(mips)
+}
+
+/// Parses a classic or DX10-extended DDS header far enough to pull out dimensions, the
+/// block format, and the mip chain. Only the BC1/BC3/BC7 formats this crate exposes are
+/// recognized; any other FourCC/DXGI_FORMAT is an error, which `load_texture_compressed`
+/// returns to its caller.
+fn parse_dds(bytes: &[u8]) -> Result<CompressedImage> {
+    if bytes.len() < 128 {
+        bail!("DDS file too small to contain a header");
+    }
+
+    let height = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 16)?;
+    let mip_map_count = read_u32(bytes, 28)?;
+    let four_cc: [u8; 4] = bytes[84..88].try_into().unwrap();
+
+    let (format, data_offset) = if four_cc == *b"DX10" {
+        let dxgi_format = read_u32(bytes, 128)?;
+        let format = match dxgi_format {
+            71 | 72 => CompressedFormat::Bc1, // BC1_UNORM / BC1_UNORM_SRGB
+            77 | 78 => CompressedFormat::Bc3, // BC3_UNORM / BC3_UNORM_SRGB
+            98 | 99 => CompressedFormat::Bc7, // BC7_UNORM / BC7_UNORM_SRGB
+            other => bail!("unsupported DX10 DXGI_FORMAT {other}"),
+        };
+        (format, 148)
+    } else {
+        let format = match &four_cc {
+            b"DXT1" => CompressedFormat::Bc1,
+            b"DXT5" => CompressedFormat::Bc3,
+            other => bail!("unsupported DDS FourCC {other:?}"),
+        };
+        (format, 128)
+    };
+
+    let data = bytes
+        .get(data_offset..)
+        .ok_or_else(|| anyhow!("DDS file too small for its header"))?;
+    let mips = mip_chain(format, width, height, data, mip_map_count)?;
+    Ok(CompressedImage {
+        format,
+        width,
+        height,
+        mips,
+    })
+}
+
+/// Parses a KTX2 container far enough to pull out dimensions, format, and the mip chain.
+/// Supercompressed (zstd/zlib) level data isn't supported, matching the "lightweight" scope
+/// here - such files are rejected with an error, which `load_texture_compressed` returns to
+/// its caller.
+fn parse_ktx2(bytes: &[u8]) -> Result<CompressedImage> {
+    let vk_format = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 20)?;
+    let height = read_u32(bytes, 24)?;
+    let level_count = read_u32(bytes, 40)?;
+    let supercompression_scheme = read_u32(bytes, 44)?;
+
+    if supercompression_scheme != 0 {
+        bail!("supercompressed KTX2 files aren't supported");
+    }
+
+    let format = match vk_format {
+        133 | 134 => CompressedFormat::Bc1,
+        137 | 138 => CompressedFormat::Bc3,
+        145 | 146 => CompressedFormat::Bc7,
+        151 | 152 => CompressedFormat::Etc2Rgba8,
+        157 | 158 => CompressedFormat::Astc4x4,
+        other => bail!("unsupported KTX2 vkFormat {other}"),
+    };
+
+    // Level index: one (byteOffset, byteLength, uncompressedByteLength) triple of u64s per
+    // mip, starting right after the fixed 68-byte header that follows the 12-byte identifier.
+    let mut mips = Vec::new();
+    for level in 0..level_count.max(1) {
+        let entry = 80 + level as usize * 24;
+        let byte_offset = read_u64(bytes, entry)? as usize;
+        let byte_length = read_u64(bytes, entry + 8)? as usize;
+        let slice = bytes
+            .get(byte_offset..byte_offset + byte_length)
+            .ok_or_else(|| anyhow!("truncated KTX2 level {level}"))?;
+        mips.push(slice.to_vec());
+    }
+
+    Ok(CompressedImage {
+        format,
+        width,
+        height,
+        mips,
+    })
+}
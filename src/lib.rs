@@ -9,6 +9,7 @@ pub use winit::event_loop::EventLoop;
 pub use winit_input_helper::WinitInputHelper as InputHelper;
 
 // Internal use
+use rayon::prelude::*;
 use wgpu::util::DeviceExt;
 use winit::{
     dpi::LogicalSize,
@@ -20,7 +21,25 @@ use winit::{
 #[cfg(target_os = "macos")]
 use winit::platform::macos::WindowExtMacOS;
 
+mod compressed_texture;
+mod mesh_pool;
+mod post_process;
+mod shader_preprocessor;
 mod texture;
+mod texture_pool;
+
+pub use compressed_texture::CompressedFormat;
+pub use mesh_pool::MeshHandle;
+pub use post_process::PostPassHandle;
+pub use shader_preprocessor::{preprocess_shader, PreprocessError};
+pub use texture_pool::TextureHandle;
+
+use shader_preprocessor::load_shader_module;
+
+use mesh_pool::MeshPool;
+use post_process::PostProcessChain;
+use texture::Texture;
+use texture_pool::TexturePool;
 
 /// Enum to use with `InputHelper.mouse_pressed` to detect user input via mouse.
 pub enum MouseButton {
@@ -46,13 +65,18 @@ pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
 
 /// Struct that defines the properties of our camera.
 /// The main components in here are:
-/// `eye (cgmath::Point3<f32>)`     - specifies where our camera is looking from.
+/// `eye (cgmath::Point3<f32>)`     - specifies where our camera is looking from (its position;
+///                                    to pan a 2D scene, move it together with `target`).
 /// `target (cgmath::Point3<f32>)`  - specifies where our camera is looking at.
+/// `zoom (f32)`                    - scales the orthographic view volume; values greater than
+///                                    `1.0` zoom in, values less than `1.0` zoom out. Only
+///                                    affects orthographic (non-perspective) cameras.
 pub struct Camera {
     pub eye: cgmath::Point3<f32>,
     pub target: cgmath::Point3<f32>,
     pub up: cgmath::Vector3<f32>,
     pub aspect: f32,
+    pub zoom: f32,
     fovy: f32,
     znear: f32,
     zfar: f32,
@@ -67,11 +91,29 @@ impl Camera {
         if self.is_perspective {
             proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
         } else {
-            proj = cgmath::ortho(-1.0, 1.0, -1.0, 1.0, self.znear, self.zfar);
+            let extent = 1.0 / self.zoom;
+            proj = cgmath::ortho(-extent, extent, -extent, extent, self.znear, self.zfar);
         }
 
         return OPENGL_TO_WGPU_MATRIX * proj * view;
     }
+
+    /// Converts a position in window pixel coordinates (origin top-left, as given by winit
+    /// mouse events) into this camera's world space, unprojected at the near plane (NDC depth 0).
+    fn screen_to_world(&self, screen_pos: (f32, f32), screen_size: (f32, f32)) -> (f32, f32) {
+        use cgmath::SquareMatrix;
+
+        let ndc_x = (screen_pos.0 / screen_size.0) * 2.0 - 1.0;
+        let ndc_y = 1.0 - (screen_pos.1 / screen_size.1) * 2.0;
+
+        let inverse_view_proj = self
+            .build_view_projection_matrix()
+            .invert()
+            .unwrap_or_else(cgmath::Matrix4::identity);
+        let world = inverse_view_proj * cgmath::Vector4::new(ndc_x, ndc_y, 0.0, 1.0);
+
+        (world.x / world.w, world.y / world.w)
+    }
 }
 
 /// Our camera uniform to store the view projection matrix.
@@ -143,17 +185,118 @@ impl Vertex {
     }
 }
 
+/// A single instance's model matrix and optional color tint, used with [`FrugInstance::add_instanced`].
+#[derive(Copy, Clone, Debug)]
+pub struct Instance {
+    pub model: [[f32; 4]; 4],
+    pub color_tint: Option<[f32; 3]>,
+}
+
+impl Instance {
+    fn to_raw(&self) -> InstanceRaw {
+        InstanceRaw {
+            model: self.model,
+            color_tint: self.color_tint.unwrap_or([1.0, 1.0, 1.0]),
+        }
+    }
+}
+
+/// The GPU-side layout of an [`Instance`], uploaded into the instance vertex buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct InstanceRaw {
+    model: [[f32; 4]; 4],
+    color_tint: [f32; 3],
+}
+
+impl InstanceRaw {
+    fn identity() -> Self {
+        use cgmath::SquareMatrix;
+        Self {
+            model: cgmath::Matrix4::identity().into(),
+            color_tint: [1.0, 1.0, 1.0],
+        }
+    }
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+            ],
+        }
+    }
+}
+
+/// Where a [`DrawableObj`] gets its vertex/index data from.
+#[derive(Clone, Copy, Debug)]
+enum DrawSource {
+    /// A range within the shared per-frame staging vertex/index buffers.
+    Staging {
+        indices_low_pos: u32,
+        indices_hi_pos: u32,
+    },
+    /// A mesh uploaded once via `FrugInstance::add_mesh`, kept alive across frames.
+    Mesh(MeshHandle),
+}
+
 /// Drawable Object struct
 /// Contains:
-/// `indices_low_pos (u32)` - The lower bound position in the indices array.
-/// `indices_hi_pos (u32)`  - The higher bound position in the indices array.
+/// `source (DrawSource)`  - Where to read vertex/index data from.
 /// `bind_group_idx (u32)`  - The index of the bind group to use.
+/// `instance_range (Range<u32>)` - The range within the instance buffer to draw.
+/// `z (f32)` - The layer used to sort this object back-to-front before drawing.
+/// `phase (PhaseHandle)` - Which render phase this object is drawn in.
+#[derive(Clone)]
 struct DrawableObj {
-    indices_low_pos: u32,
-    indices_hi_pos: u32,
+    source: DrawSource,
     bind_group_idx: Option<usize>,
+    instance_range: std::ops::Range<u32>,
+    z: f32,
+    phase: PhaseHandle,
+}
+
+/// A named, ordered step of the render graph (e.g. `Opaque`, `Transparent`, `UI`). Phases run
+/// in registration order; see [`FrugInstance::add_phase`].
+struct RenderPhase {
+    #[allow(dead_code)]
+    name: String,
+    /// Whether this phase's objects may be freely reordered and split across the rayon
+    /// thread pool for encoding (safe for opaque, depth-tested draws), or must be encoded in
+    /// their sorted order to preserve correct alpha blending (transparent/UI draws).
+    parallel: bool,
 }
 
+/// A handle to a render phase registered with [`FrugInstance::add_phase`], used to tag draw
+/// calls via [`FrugInstance::set_draw_phase`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PhaseHandle(pub(crate) usize);
+
 /// The Frug instance.
 /// Contains the surface in which we draw, the device we're using, the queue, the surface configuration, surface size, window, background color, and render pipeline.
 pub struct FrugInstance {
@@ -165,20 +308,330 @@ pub struct FrugInstance {
     window: Window,
     background_color: wgpu::Color,
     render_pipeline_textures: wgpu::RenderPipeline,
+    render_pipeline_textures_no_depth: wgpu::RenderPipeline,
     render_pipeline_colors: wgpu::RenderPipeline,
+    render_pipeline_colors_no_depth: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
     staging_vertices: Vec<Vertex>,
-    staging_indices: Vec<u16>,
+    staging_indices: Vec<u32>,
+    staging_instances: Vec<InstanceRaw>,
     num_indices: u32,
-    texture_bind_group_layout: wgpu::BindGroupLayout,
-    diffuse_bind_groups: Vec<wgpu::BindGroup>,
+    texture_pool: TexturePool,
+    mesh_pool: MeshPool,
     drawable_objects: Vec<DrawableObj>,
     pub camera: Camera,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    depth_texture: wgpu::Texture,
+    depth_texture_view: wgpu::TextureView,
+    depth_testing_enabled: bool,
+    render_pipeline_lit: wgpu::RenderPipeline,
+    render_pipeline_lit_no_depth: wgpu::RenderPipeline,
+    lit_texture_bind_group_layout: wgpu::BindGroupLayout,
+    lit_bind_groups: Vec<wgpu::BindGroup>,
+    lit_drawable_objects: Vec<LitDrawableObj>,
+    ambient_light: [f32; 3],
+    lights: Vec<Light>,
+    light_buffer: wgpu::Buffer,
+    light_bind_group: wgpu::BindGroup,
+    post_process: PostProcessChain,
     exit_requested: bool,
+    fixed_timestep: Option<f32>,
+    timestep_accumulator: f32,
+    compressed_texture_features: wgpu::Features,
+    phases: Vec<RenderPhase>,
+    current_phase: PhaseHandle,
+}
+
+/// The built-in phase opaque draws are assigned to by default (see [`FrugInstance::add_phase`]).
+const PHASE_OPAQUE: PhaseHandle = PhaseHandle(0);
+/// The built-in phase meant for alpha-blended draws that must preserve back-to-front order.
+const PHASE_TRANSPARENT: PhaseHandle = PhaseHandle(1);
+/// The built-in phase meant for screen-space UI draws, the last built-in phase and order-preserving.
+const PHASE_UI: PhaseHandle = PhaseHandle(2);
+
+/// The maximum number of lights uploaded to the lighting shader per frame.
+const MAX_LIGHTS: usize = 8;
+
+/// A point light used by the normal-mapped lit pipeline (see `FrugInstance::add_light`).
+#[derive(Copy, Clone, Debug)]
+pub struct Light {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightRaw {
+    position: [f32; 3],
+    _pad0: f32,
+    color: [f32; 3],
+    intensity: f32,
+}
+
+impl From<Light> for LightRaw {
+    fn from(light: Light) -> Self {
+        Self {
+            position: light.position,
+            _pad0: 0.0,
+            color: light.color,
+            intensity: light.intensity,
+        }
+    }
+}
+
+/// Mirrors `LightUniform` in `shader_lit.wgsl`: an ambient term plus up to `MAX_LIGHTS`
+/// point lights.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightUniform {
+    ambient: [f32; 3],
+    light_count: u32,
+    lights: [LightRaw; MAX_LIGHTS],
+}
+
+impl LightUniform {
+    fn new() -> Self {
+        Self {
+            ambient: [0.1, 0.1, 0.1],
+            light_count: 0,
+            lights: [LightRaw::from(Light {
+                position: [0.0, 0.0, 0.0],
+                color: [0.0, 0.0, 0.0],
+                intensity: 0.0,
+            }); MAX_LIGHTS],
+        }
+    }
+
+    fn update(&mut self, ambient: [f32; 3], lights: &[Light]) {
+        self.ambient = ambient;
+        self.light_count = lights.len().min(MAX_LIGHTS) as u32;
+        for (slot, light) in self.lights.iter_mut().zip(lights.iter()) {
+            *slot = LightRaw::from(*light);
+        }
+    }
+}
+
+/// A lightweight, copyable reference to a texture loaded via `FrugInstance::load_lit_texture`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LitTextureHandle(usize);
+
+/// A drawable object rendered through the normal-mapped lit pipeline.
+struct LitDrawableObj {
+    indices_low_pos: u32,
+    indices_hi_pos: u32,
+    bind_group_idx: usize,
+    instance_range: std::ops::Range<u32>,
+}
+
+/// The depth/stencil format used for the optional depth buffer.
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// In fixed-timestep mode, the per-frame accumulator is clamped to this many seconds of real
+/// elapsed time, so a stalled frame (a breakpoint, a dropped window) can't trigger a
+/// spiral-of-death burst of catch-up update calls afterwards.
+const MAX_ACCUMULATED_FRAME_TIME: f32 = 0.25;
+
+/// Creates a depth texture (and its view) sized to match the surface configuration.
+fn create_depth_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let size = wgpu::Extent3d {
+        width: config.width.max(1),
+        height: config.height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Depth Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let depth_texture_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    (depth_texture, depth_texture_view)
+}
+
+/// The borrowed GPU state needed to encode a phase's draw calls, grouped so it can be shared
+/// across the rayon thread pool without requiring `FrugInstance` itself to be `Sync` (its
+/// `winit::window::Window` field isn't, on every platform).
+struct PhaseRenderContext<'a> {
+    device: &'a wgpu::Device,
+    background_color: wgpu::Color,
+    scene_view: &'a wgpu::TextureView,
+    depth_view: Option<&'a wgpu::TextureView>,
+    instance_buffer: &'a wgpu::Buffer,
+    vertex_buffer: &'a wgpu::Buffer,
+    index_buffer: &'a wgpu::Buffer,
+    camera_bind_group: &'a wgpu::BindGroup,
+    texture_pool: &'a TexturePool,
+    mesh_pool: &'a MeshPool,
+    pipeline_textures: &'a wgpu::RenderPipeline,
+    pipeline_textures_no_depth: &'a wgpu::RenderPipeline,
+    pipeline_colors: &'a wgpu::RenderPipeline,
+    pipeline_colors_no_depth: &'a wgpu::RenderPipeline,
+}
+
+/// Begins the frame by clearing the scene's color (and, if enabled, depth) attachments, so
+/// every phase chunk encoded afterwards - on any thread, in any order - can uniformly use
+/// `wgpu::LoadOp::Load` instead of racing to be the one that clears.
+fn encode_clear_pass(ctx: &PhaseRenderContext) -> wgpu::CommandBuffer {
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Clear Pass Encoder"),
+        });
+
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Clear Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: ctx.scene_view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(ctx.background_color),
+                store: true,
+            },
+        })],
+        depth_stencil_attachment: ctx.depth_view.map(|view| wgpu::RenderPassDepthStencilAttachment {
+            view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+    });
+
+    encoder.finish()
+}
+
+/// Encodes one chunk of already-sorted `DrawableObj`s into its own command buffer, batching
+/// by pipeline/bind group to minimize state changes the same way the original single-pass
+/// renderer did. Safe to call concurrently with other chunks of the same phase, since every
+/// chunk loads rather than clears its attachments and GPU submission order - not encoding
+/// order - is what `queue.submit`'s buffer list actually honors.
+fn encode_drawable_chunk(
+    ctx: &PhaseRenderContext,
+    objects: &[&DrawableObj],
+) -> wgpu::CommandBuffer {
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Phase Chunk Encoder"),
+        });
+
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Phase Chunk Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.scene_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: ctx.depth_view.map(|view| {
+                wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }
+            }),
+        });
+
+        render_pass.set_vertex_buffer(1, ctx.instance_buffer.slice(..));
+
+        let mut current_pipeline_is_textured: Option<bool> = None;
+        let mut current_bind_group_idx: Option<usize> = None;
+        // `None` means unset, `Some(None)` means bound to the staging buffers,
+        // `Some(Some(handle))` means bound to that pooled mesh's own buffers.
+        let mut current_vertex_source: Option<Option<MeshHandle>> = None;
+
+        for drawable_obj in objects {
+            let is_textured = drawable_obj.bind_group_idx.is_some();
+            let mut camera_bind_group_idx = 0;
+
+            if current_pipeline_is_textured != Some(is_textured) {
+                let pipeline = match (is_textured, ctx.depth_view.is_some()) {
+                    (true, true) => ctx.pipeline_textures,
+                    (true, false) => ctx.pipeline_textures_no_depth,
+                    (false, true) => ctx.pipeline_colors,
+                    (false, false) => ctx.pipeline_colors_no_depth,
+                };
+                render_pass.set_pipeline(pipeline);
+                current_pipeline_is_textured = Some(is_textured);
+                // Force the bind group to be rebound too, since it's only valid
+                // alongside the textured pipeline.
+                current_bind_group_idx = None;
+            }
+
+            if let Some(idx) = drawable_obj.bind_group_idx {
+                if current_bind_group_idx != Some(idx) {
+                    render_pass.set_bind_group(0, ctx.texture_pool.bind_group_by_index(idx), &[]);
+                    current_bind_group_idx = Some(idx);
+                }
+                // update to camera bind group index so it is in the correct binding position
+                camera_bind_group_idx = 1;
+            }
+
+            // camera bind group
+            render_pass.set_bind_group(camera_bind_group_idx, ctx.camera_bind_group, &[]);
+
+            match &drawable_obj.source {
+                DrawSource::Staging {
+                    indices_low_pos,
+                    indices_hi_pos,
+                } => {
+                    if current_vertex_source != Some(None) {
+                        render_pass.set_vertex_buffer(0, ctx.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(
+                            ctx.index_buffer.slice(..),
+                            wgpu::IndexFormat::Uint32,
+                        );
+                        current_vertex_source = Some(None);
+                    }
+
+                    render_pass.draw_indexed(
+                        *indices_low_pos..*indices_hi_pos,
+                        0,
+                        drawable_obj.instance_range.clone(),
+                    );
+                }
+                DrawSource::Mesh(handle) => {
+                    let (vertex_buffer, index_buffer, num_indices) = ctx.mesh_pool.buffers(*handle);
+
+                    if current_vertex_source != Some(Some(*handle)) {
+                        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                        render_pass
+                            .set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                        current_vertex_source = Some(Some(*handle));
+                    }
+
+                    render_pass.draw_indexed(
+                        0..num_indices,
+                        0,
+                        drawable_obj.instance_range.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    encoder.finish()
 }
 
 /// Implementation of FrugInstance methods
@@ -199,7 +652,7 @@ impl FrugInstance {
             a: 1.0,
         };
         let vertices: &[Vertex] = &[];
-        let indices: &[u16] = &[];
+        let indices: &[u32] = &[];
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
 
@@ -214,11 +667,18 @@ impl FrugInstance {
             .await
             .expect("Failed to find an appropiate adapter.");
 
+        // Request whichever compressed-texture features the adapter supports, so
+        // `load_texture_compressed` can upload BC/ETC2/ASTC data directly when available.
+        let compressed_texture_features = adapter.features()
+            & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC);
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
-                    features: wgpu::Features::empty(),
+                    features: compressed_texture_features,
                     limits: wgpu::Limits::default(),
                 },
                 None,
@@ -247,11 +707,25 @@ impl FrugInstance {
         surface.configure(&device, &config);
 
         // load texture shader (to use with textured vertices)
-        let shader_texture =
-            device.create_shader_module(wgpu::include_wgsl!("shader_texture.wgsl"));
+        let shader_texture = load_shader_module(
+            &device,
+            "shader_texture.wgsl",
+            include_str!("shader_texture.wgsl"),
+        );
 
         // load color shader (to use with colored vertices)
-        let shader_color = device.create_shader_module(wgpu::include_wgsl!("shader_color.wgsl"));
+        let shader_color = load_shader_module(
+            &device,
+            "shader_color.wgsl",
+            include_str!("shader_color.wgsl"),
+        );
+
+        // load lit shader (to use with normal-mapped textured vertices)
+        let shader_lit = load_shader_module(
+            &device,
+            "shader_lit.wgsl",
+            include_str!("shader_lit.wgsl"),
+        );
 
         // Camera
         let camera = Camera {
@@ -259,6 +733,7 @@ impl FrugInstance {
             target: (0.0, 0.0, 0.0).into(),
             up: cgmath::Vector3::unit_y(),
             aspect: config.width as f32 / config.height as f32,
+            zoom: 1.0,
             fovy: 45.0,
             znear: 0.1,
             zfar: 100.0,
@@ -322,6 +797,80 @@ impl FrugInstance {
                 ],
             });
 
+        // we use this to load diffuse + normal textures for the lit pipeline
+        let lit_texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("lit_texture_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        // Lights are uploaded as a single uniform buffer, rewritten each frame in `update`.
+        let mut light_uniform = LightUniform::new();
+        light_uniform.update([0.1, 0.1, 0.1], &[]);
+
+        let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Light Buffer"),
+            contents: bytemuck::cast_slice(&[light_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let light_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Light bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light bind group"),
+            layout: &light_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: light_buffer.as_entire_binding(),
+            }],
+        });
+
         // the render pipeline layout to use with textures.
         let render_pipeline_textures_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -338,59 +887,40 @@ impl FrugInstance {
                 push_constant_ranges: &[],
             });
 
-        // our render pipeline to use with textures
-        let render_pipeline_textures =
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline Textures"),
-                layout: Some(&render_pipeline_textures_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader_texture,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::desc()],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader_texture,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
+        // the render pipeline layout to use with normal-mapped lit textures.
+        let render_pipeline_lit_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &lit_texture_bind_group_layout,
+                    &light_bind_group_layout,
+                    &camera_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
             });
 
-        // our render pipeline to use with colors
-        let render_pipeline_colors =
+        // Builds a render pipeline for one of our shaders, optionally with depth testing -
+        // we keep a depth-testing and a no-depth-testing variant of each so
+        // `set_depth_testing` can pick the right one without rebuilding pipelines at runtime.
+        let build_pipeline = |label: &str,
+                               shader: &wgpu::ShaderModule,
+                               layout: &wgpu::PipelineLayout,
+                               blend: wgpu::BlendState,
+                               depth_stencil: Option<wgpu::DepthStencilState>| {
             device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline Colors"),
-                layout: Some(&render_pipeline_colors_layout),
+                label: Some(label),
+                layout: Some(layout),
                 vertex: wgpu::VertexState {
-                    module: &shader_color,
+                    module: shader,
                     entry_point: "vs_main",
-                    buffers: &[Vertex::desc()],
+                    buffers: &[Vertex::desc(), InstanceRaw::desc()],
                 },
                 fragment: Some(wgpu::FragmentState {
-                    module: &shader_color,
+                    module: shader,
                     entry_point: "fs_main",
                     targets: &[Some(wgpu::ColorTargetState {
                         format: config.format,
-                        blend: Some(wgpu::BlendState::REPLACE),
+                        blend: Some(blend),
                         write_mask: wgpu::ColorWrites::ALL,
                     })],
                 }),
@@ -403,14 +933,71 @@ impl FrugInstance {
                     polygon_mode: wgpu::PolygonMode::Fill,
                     conservative: false,
                 },
-                depth_stencil: None,
+                depth_stencil,
                 multisample: wgpu::MultisampleState {
                     count: 1,
                     mask: !0,
                     alpha_to_coverage_enabled: false,
                 },
                 multiview: None,
-            });
+            })
+        };
+
+        let depth_state = wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+
+        // our render pipeline to use with textures
+        let render_pipeline_textures = build_pipeline(
+            "Render Pipeline Textures",
+            &shader_texture,
+            &render_pipeline_textures_layout,
+            wgpu::BlendState::ALPHA_BLENDING,
+            Some(depth_state.clone()),
+        );
+        let render_pipeline_textures_no_depth = build_pipeline(
+            "Render Pipeline Textures (No Depth Test)",
+            &shader_texture,
+            &render_pipeline_textures_layout,
+            wgpu::BlendState::ALPHA_BLENDING,
+            None,
+        );
+
+        // our render pipeline to use with colors
+        let render_pipeline_colors = build_pipeline(
+            "Render Pipeline Colors",
+            &shader_color,
+            &render_pipeline_colors_layout,
+            wgpu::BlendState::REPLACE,
+            Some(depth_state.clone()),
+        );
+        let render_pipeline_colors_no_depth = build_pipeline(
+            "Render Pipeline Colors (No Depth Test)",
+            &shader_color,
+            &render_pipeline_colors_layout,
+            wgpu::BlendState::REPLACE,
+            None,
+        );
+
+        // our render pipeline to use with normal-mapped lit textures
+        let render_pipeline_lit = build_pipeline(
+            "Render Pipeline Lit",
+            &shader_lit,
+            &render_pipeline_lit_layout,
+            wgpu::BlendState::ALPHA_BLENDING,
+            Some(depth_state.clone()),
+        );
+        let render_pipeline_lit_no_depth = build_pipeline(
+            "Render Pipeline Lit (No Depth Test)",
+            &shader_lit,
+            &render_pipeline_lit_layout,
+            wgpu::BlendState::ALPHA_BLENDING,
+            None,
+        );
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
@@ -424,8 +1011,37 @@ impl FrugInstance {
             usage: wgpu::BufferUsages::INDEX,
         });
 
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: &[],
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
         let num_indices = indices.len() as u32;
 
+        let (depth_texture, depth_texture_view) = create_depth_texture(&device, &config);
+
+        let post_process =
+            PostProcessChain::new(&device, config.format, (config.width, config.height));
+
+        // Built-in phases: Opaque draws are depth-tested, so their relative encoding order
+        // doesn't affect the final image and they can be freely split across threads;
+        // Transparent and UI must keep their sorted (back-to-front / submission) order.
+        let phases = vec![
+            RenderPhase {
+                name: "Opaque".to_string(),
+                parallel: true,
+            },
+            RenderPhase {
+                name: "Transparent".to_string(),
+                parallel: false,
+            },
+            RenderPhase {
+                name: "UI".to_string(),
+                parallel: false,
+            },
+        ];
+
         Self {
             window,
             surface,
@@ -435,20 +1051,42 @@ impl FrugInstance {
             size,
             background_color,
             render_pipeline_textures,
+            render_pipeline_textures_no_depth,
             render_pipeline_colors,
+            render_pipeline_colors_no_depth,
             vertex_buffer,
             index_buffer,
+            instance_buffer,
             staging_vertices: Vec::new(),
             staging_indices: Vec::new(),
+            staging_instances: Vec::new(),
             num_indices,
-            texture_bind_group_layout,
-            diffuse_bind_groups: Vec::new(),
+            texture_pool: TexturePool::new(texture_bind_group_layout),
+            mesh_pool: MeshPool::new(),
             drawable_objects: Vec::new(),
             camera,
             camera_uniform,
             camera_buffer,
             camera_bind_group,
+            depth_texture,
+            depth_texture_view,
+            depth_testing_enabled: true,
+            render_pipeline_lit,
+            render_pipeline_lit_no_depth,
+            lit_texture_bind_group_layout,
+            lit_bind_groups: Vec::new(),
+            lit_drawable_objects: Vec::new(),
+            ambient_light: [0.1, 0.1, 0.1],
+            lights: Vec::new(),
+            light_buffer,
+            light_bind_group,
+            post_process,
             exit_requested: false,
+            fixed_timestep: None,
+            timestep_accumulator: 0.0,
+            compressed_texture_features,
+            phases,
+            current_phase: PHASE_OPAQUE,
         }
     }
 
@@ -459,76 +1097,307 @@ impl FrugInstance {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+
+            let (depth_texture, depth_texture_view) =
+                create_depth_texture(&self.device, &self.config);
+            self.depth_texture = depth_texture;
+            self.depth_texture_view = depth_texture_view;
+
+            self.post_process
+                .resize(&self.device, (new_size.width, new_size.height));
         }
     }
 
-    /// Renders all textured objects based on data on buffers.
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
+    /// Toggles depth testing.
+    ///
+    /// When enabled (the default), sprites are layered by their vertex `z` coordinate
+    /// rather than by submission order. Disable this if you rely on painter's-algorithm
+    /// ordering instead.
+    pub fn set_depth_testing(&mut self, enabled: bool) {
+        self.depth_testing_enabled = enabled;
+    }
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    /// Chooses between the default wait-driven update loop and a fixed-timestep one.
+    ///
+    /// `Some(dt)` switches `run` to accumulate real elapsed time and invoke the update
+    /// closure a whole number of times per frame at the fixed step `dt` (in seconds, e.g.
+    /// `1.0 / 60.0`), which is what frame-rate-independent game logic - a bouncing ball, a
+    /// Breakout-style paddle - needs to stay deterministic. This also switches the event loop
+    /// to `ControlFlow::Poll`, so rendering happens continuously instead of only on input.
+    ///
+    /// `None` (the default) restores the original behavior: the update closure runs once per
+    /// `MainEventsCleared` with the real, variable frame delta, and the loop waits for events.
+    pub fn set_fixed_timestep(&mut self, dt: Option<f32>) {
+        self.fixed_timestep = dt;
+        self.timestep_accumulator = 0.0;
+    }
+
+    /// Registers a custom render phase, returning a handle draw calls can target via
+    /// `set_draw_phase`. Phases run in registration order, after the built-in `Opaque`,
+    /// `Transparent`, and `UI` phases.
+    ///
+    /// `parallel` should be `true` only if objects in this phase may be freely reordered and
+    /// split across threads for encoding - safe for depth-tested opaque draws, but not for
+    /// alpha-blended ones that rely on back-to-front submission order.
+    pub fn add_phase(&mut self, name: &str, parallel: bool) -> PhaseHandle {
+        self.phases.push(RenderPhase {
+            name: name.to_string(),
+            parallel,
+        });
+        PhaseHandle(self.phases.len() - 1)
+    }
+
+    /// Sets which phase subsequent draw calls (`add_tex_rect`, `add_colored_rect`, ...) are
+    /// assigned to, until changed again. Defaults to `phase_opaque()`.
+    pub fn set_draw_phase(&mut self, phase: PhaseHandle) {
+        self.current_phase = phase;
+    }
 
+    /// The built-in phase draw calls are assigned to by default: depth-tested and safe to
+    /// encode across threads.
+    pub fn phase_opaque(&self) -> PhaseHandle {
+        PHASE_OPAQUE
+    }
+
+    /// The built-in phase meant for alpha-blended draws that must preserve back-to-front
+    /// submission order, run after `phase_opaque`.
+    pub fn phase_transparent(&self) -> PhaseHandle {
+        PHASE_TRANSPARENT
+    }
+
+    /// The built-in phase meant for screen-space UI draws, run last of the built-in phases
+    /// (phases added via `add_phase` run after it) and order-preserving.
+    pub fn phase_ui(&self) -> PhaseHandle {
+        PHASE_UI
+    }
+
+    /// Replaces the current camera wholesale, e.g. to swap in a different view for a cutscene.
+    ///
+    /// `self.camera` is also a public field, so panning/zooming frame to frame can just mutate
+    /// it (or go through `camera_mut`) without calling this.
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = camera;
+    }
+
+    /// Returns a mutable reference to the camera, for panning (`camera.eye` and
+    /// `camera.target` moved together), zooming (`camera.zoom`) or rotating (`camera.up`) the view.
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Converts a position in window pixel coordinates (origin top-left, as given by winit
+    /// mouse events) into world space, accounting for the camera's current pan/zoom.
+    pub fn screen_to_world(&self, screen_x: f32, screen_y: f32) -> (f32, f32) {
+        self.camera
+            .screen_to_world((screen_x, screen_y), (self.size.width as f32, self.size.height as f32))
+    }
+
+    /// Appends a full-screen post-processing pass to the end of the chain run after the scene
+    /// is drawn, returning a handle to it. The scene (and any earlier pass's output) is bound
+    /// at `@group(0) @binding(0)` (`texture_2d<f32>`) with its sampler at `@binding(1)`; up to
+    /// `MAX_POST_PASS_PARAMS` per-frame f32 params (set via `set_post_pass_param`) are exposed
+    /// as `array<vec4<f32>, 2>` (param `i` is `params[i / 4][i % 4]`) in a uniform block at
+    /// `@group(1) @binding(0)`. The fragment entry point must be named `fs_main`, matching
+    /// every other shader in this crate.
+    ///
+    /// * `shader_src`   - WGSL source for the pass's `fs_main` fragment shader. Run through
+    ///                    [`preprocess_shader`] before compiling, so `#define`/`#ifdef`
+    ///                    directives take effect; no include map is given here, so an
+    ///                    `#include` panics unless flattened with `preprocess_shader` first.
+    /// * `scale`        - The pass's output resolution relative to the window size.
+    /// * `filter`       - The filter used when the next pass samples this pass's output.
+    /// * `address_mode` - The wrap mode used when the next pass samples outside this pass's
+    ///                    output.
+    ///
+    /// The last pass in the chain renders straight to the surface, so its `scale`, `filter`
+    /// and `address_mode` have no effect.
+    /// * `param_names`  - Names for this pass's per-frame f32 params.
+    pub fn add_post_pass(
+        &mut self,
+        shader_src: &str,
+        scale: f32,
+        filter: wgpu::FilterMode,
+        address_mode: wgpu::AddressMode,
+        param_names: &[&str],
+    ) -> PostPassHandle {
+        self.post_process.add_pass(
+            &self.device,
+            shader_src,
+            scale,
+            filter,
+            address_mode,
+            param_names,
+        )
+    }
+
+    /// Sets a named per-frame param (declared via `add_post_pass`) on a post-processing pass.
+    pub fn set_post_pass_param(&mut self, pass: PostPassHandle, name: &str, value: f32) {
+        self.post_process.set_pass_param(pass, name, value);
+    }
+
+    /// Encodes the lit (normal-mapped) pass over the same staging buffers as the main phases,
+    /// since it uses its own pipeline/bind group layout and is rarer than plain
+    /// textured/colored draws. Always encoded on the main thread, since there's normally far
+    /// fewer of these than there are phase objects worth parallelizing.
+    fn encode_lit_pass(&mut self) -> wgpu::CommandBuffer {
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
+                label: Some("Lit Pass Encoder"),
             });
 
-        // draw our objects
-        let mut render_pass_op = wgpu::LoadOp::Clear(self.background_color);
-        for drawable_obj in &self.drawable_objects {
+        {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
+                label: Some("Lit Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: self.post_process.scene_view(),
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: render_pass_op,
+                        load: wgpu::LoadOp::Load,
                         store: true,
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: self.depth_testing_enabled.then(|| {
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth_texture_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        }),
+                        stencil_ops: None,
+                    }
+                }),
             });
 
-            let mut camera_bind_group_idx = 0;
-
-            // texture bind group
-            match drawable_obj.bind_group_idx {
-                Some(idx) => {
-                    render_pass.set_pipeline(&self.render_pipeline_textures);
-                    render_pass.set_bind_group(0, &self.diffuse_bind_groups[idx], &[]);
+            if !self.lit_drawable_objects.is_empty() {
+                self.lit_drawable_objects
+                    .sort_by_key(|obj| obj.bind_group_idx);
+
+                let pipeline = if self.depth_testing_enabled {
+                    &self.render_pipeline_lit
+                } else {
+                    &self.render_pipeline_lit_no_depth
+                };
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                render_pass
+                    .set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.set_bind_group(2, &self.camera_bind_group, &[]);
+                render_pass.set_bind_group(1, &self.light_bind_group, &[]);
+
+                let mut current_bind_group_idx: Option<usize> = None;
+                for lit_obj in &self.lit_drawable_objects {
+                    if current_bind_group_idx != Some(lit_obj.bind_group_idx) {
+                        render_pass.set_bind_group(
+                            0,
+                            &self.lit_bind_groups[lit_obj.bind_group_idx],
+                            &[],
+                        );
+                        current_bind_group_idx = Some(lit_obj.bind_group_idx);
+                    }
 
-                    // update to camera bind group index so it is in the correct binding position
-                    camera_bind_group_idx = 1;
-                }
-                None => {
-                    // We'll use the render pipeline with colors instead of textures
-                    render_pass.set_pipeline(&self.render_pipeline_colors);
+                    render_pass.draw_indexed(
+                        lit_obj.indices_low_pos..lit_obj.indices_hi_pos,
+                        0,
+                        lit_obj.instance_range.clone(),
+                    );
                 }
             }
+        }
 
-            // camera bind group
-            render_pass.set_bind_group(camera_bind_group_idx, &self.camera_bind_group, &[]);
+        encoder.finish()
+    }
+
+    /// Renders all drawable objects, phase by phase, into the offscreen scene target, then
+    /// runs post-processing onto the swapchain surface.
+    ///
+    /// Phases run in registration order (built-in `Opaque`, `Transparent`, `UI`, then any
+    /// added via `add_phase`). A phase marked `parallel` is split into chunks encoded across
+    /// the rayon thread pool; other phases encode as a single ordered chunk, preserving
+    /// back-to-front/submission order. Every phase's resulting command buffers are collected
+    /// in phase order and submitted together in one `queue.submit` call - GPU execution order
+    /// is governed by that submission order, not by which thread encoded which buffer first.
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Sort back-to-front by layer first, so alpha-blended quads composite in the right
+        // order, then batch same-layer objects by pipeline/bind group to minimize state
+        // changes within each phase's render pass.
+        self.drawable_objects.sort_by(|a, b| {
+            a.z.partial_cmp(&b.z)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.bind_group_idx.is_some().cmp(&b.bind_group_idx.is_some()))
+                .then_with(|| a.bind_group_idx.cmp(&b.bind_group_idx))
+        });
+
+        let ctx = PhaseRenderContext {
+            device: &self.device,
+            background_color: self.background_color,
+            scene_view: self.post_process.scene_view(),
+            depth_view: self.depth_testing_enabled.then_some(&self.depth_texture_view),
+            instance_buffer: &self.instance_buffer,
+            vertex_buffer: &self.vertex_buffer,
+            index_buffer: &self.index_buffer,
+            camera_bind_group: &self.camera_bind_group,
+            texture_pool: &self.texture_pool,
+            mesh_pool: &self.mesh_pool,
+            pipeline_textures: &self.render_pipeline_textures,
+            pipeline_textures_no_depth: &self.render_pipeline_textures_no_depth,
+            pipeline_colors: &self.render_pipeline_colors,
+            pipeline_colors_no_depth: &self.render_pipeline_colors_no_depth,
+        };
+
+        let mut command_buffers = vec![encode_clear_pass(&ctx)];
 
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for (phase_idx, phase) in self.phases.iter().enumerate() {
+            let objects: Vec<&DrawableObj> = self
+                .drawable_objects
+                .iter()
+                .filter(|obj| obj.phase.0 == phase_idx)
+                .collect();
 
-            render_pass.draw_indexed(
-                drawable_obj.indices_low_pos..drawable_obj.indices_hi_pos,
-                0,
-                0..1,
-            );
+            if objects.is_empty() {
+                continue;
+            }
 
-            render_pass_op = wgpu::LoadOp::Load;
+            if phase.parallel {
+                let chunk_count = rayon::current_num_threads().min(objects.len()).max(1);
+                let chunk_size = (objects.len() + chunk_count - 1) / chunk_count;
+                command_buffers.extend(
+                    objects
+                        .par_chunks(chunk_size)
+                        .map(|chunk| encode_drawable_chunk(&ctx, chunk))
+                        .collect::<Vec<_>>(),
+                );
+            } else {
+                command_buffers.push(encode_drawable_chunk(&ctx, &objects));
+            }
         }
 
+        command_buffers.push(self.encode_lit_pass());
+
         // Clear objects
         self.drawable_objects.clear();
+        self.lit_drawable_objects.clear();
+        self.lights.clear();
+
+        // Run the post-processing chain (or an implicit blit if it's empty) to get the scene
+        // from its offscreen target onto the swapchain surface.
+        let mut post_encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Post Process Encoder"),
+            });
+        self.post_process.run(&self.queue, &mut post_encoder, &view);
+        command_buffers.push(post_encoder.finish());
 
-        // submit the encoder to the queue & present it on the screen
-        self.queue.submit(std::iter::once(encoder.finish()));
+        // submit every phase's command buffers to the queue, in phase order, & present
+        self.queue.submit(command_buffers);
         output.present();
 
         Ok(())
@@ -542,6 +1411,11 @@ impl FrugInstance {
             0,
             bytemuck::cast_slice(&[self.camera_uniform]),
         );
+
+        let mut light_uniform = LightUniform::new();
+        light_uniform.update(self.ambient_light, &self.lights);
+        self.queue
+            .write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&[light_uniform]));
     }
 
     /// Signify that the event loop should be exited when next possible.
@@ -599,7 +1473,7 @@ impl FrugInstance {
         self.window.set_inner_size(LogicalSize::new(width, height));
     }
 
-    /// Updates the vertex and index buffers with the staging data.
+    /// Updates the vertex, index, and instance buffers with the staging data.
     pub fn update_buffers(&mut self) {
         self.vertex_buffer = self
             .device
@@ -617,26 +1491,69 @@ impl FrugInstance {
                 usage: wgpu::BufferUsages::INDEX,
             });
 
+        self.instance_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Instance Buffer"),
+                contents: bytemuck::cast_slice(&self.staging_instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
         self.num_indices = self.staging_indices.len() as u32;
     }
 
     /// Adds a set of vertices and indices to the staging data.
-    pub fn add_colored_vertices(&mut self, vertices: &[Vertex], indices: &[u16]) {
+    pub fn add_colored_vertices(&mut self, vertices: &[Vertex], indices: &[u32]) {
         // Add the vertices to the drawable objects vector
         let low_bound = self.staging_indices.len() as u32;
+        let instance_range = self.push_identity_instance();
         self.drawable_objects.push(DrawableObj {
-            indices_low_pos: low_bound,
-            indices_hi_pos: low_bound + indices.len() as u32,
+            source: DrawSource::Staging {
+                indices_low_pos: low_bound,
+                indices_hi_pos: low_bound + indices.len() as u32,
+            },
             bind_group_idx: None,
+            instance_range,
+            z: vertices.first().map(|v| v.position[2]).unwrap_or(0.0),
+            phase: self.current_phase,
+        });
+
+        self.add_staging_indexed_vertices(vertices, indices);
+    }
+
+    /// Draws a set of vertices and indices once per instance, applying each instance's model
+    /// matrix and color tint on the GPU instead of re-uploading the geometry per copy.
+    pub fn add_instanced(
+        &mut self,
+        vertices: &[Vertex],
+        indices: &[u32],
+        texture: Option<TextureHandle>,
+        instances: &[Instance],
+    ) {
+        let low_bound = self.staging_indices.len() as u32;
+        let instance_low = self.staging_instances.len() as u32;
+        self.staging_instances
+            .extend(instances.iter().map(Instance::to_raw));
+        let instance_hi = self.staging_instances.len() as u32;
+
+        self.drawable_objects.push(DrawableObj {
+            source: DrawSource::Staging {
+                indices_low_pos: low_bound,
+                indices_hi_pos: low_bound + indices.len() as u32,
+            },
+            bind_group_idx: texture.map(|handle| handle.index),
+            instance_range: instance_low..instance_hi,
+            z: vertices.first().map(|v| v.position[2]).unwrap_or(0.0),
+            phase: self.current_phase,
         });
 
         self.add_staging_indexed_vertices(vertices, indices);
     }
 
     /// Adds a set of vertices and indices to the staging data.
-    fn add_staging_indexed_vertices(&mut self, vertices: &[Vertex], indices: &[u16]) {
+    fn add_staging_indexed_vertices(&mut self, vertices: &[Vertex], indices: &[u32]) {
         // update the indices to match the number of current vertices
-        let offset: u16 = self.staging_vertices.len() as u16;
+        let offset: u32 = self.staging_vertices.len() as u32;
         for index in indices {
             self.staging_indices.push(index + offset);
         }
@@ -644,73 +1561,136 @@ impl FrugInstance {
         self.staging_vertices.extend(vertices);
     }
 
-    /// Clears the staging buffers data so the next frame is empty.
+    /// Pushes a single identity instance (no transform, no tint) and returns its range,
+    /// for drawable objects that weren't created through `add_instanced`.
+    fn push_identity_instance(&mut self) -> std::ops::Range<u32> {
+        let low = self.staging_instances.len() as u32;
+        self.staging_instances.push(InstanceRaw::identity());
+        low..low + 1
+    }
+
+    /// Clears the staging buffers and queued drawable objects/lights so the next frame (or, in
+    /// fixed-timestep mode, the next tick) starts empty. `render()` also clears the queued
+    /// objects/lights after drawing them, but this additionally clears them here so that calling
+    /// `clear()` + `add_*` + `update_buffers()` several times between `render()` calls - the
+    /// normal pattern under [`Self::set_fixed_timestep`] - doesn't leave stale objects from an
+    /// earlier tick pointing at staging ranges a later tick has since overwritten.
     pub fn clear(&mut self) {
         self.staging_vertices.clear();
         self.staging_indices.clear();
+        self.staging_instances.clear();
+        self.drawable_objects.clear();
+        self.lit_drawable_objects.clear();
+        self.lights.clear();
     }
 
-    /// Adds a rectangle to the staging data using a texture.
+    /// Adds a rectangle to the staging data using the whole of a texture.
     /// Receives:
-    /// * `x (f32)`             - The x origin of the rectangle.
-    /// * `y (f32)`             - The y origin of the rectangle.
-    /// * `w (f32)`             - The width of the rectangle.
-    /// * `h (f32)`             - The height of the rectangle.
-    /// * `texture_index (u16)` - The index of the texture we're drawing.
+    /// * `x (f32)`                    - The x origin of the rectangle.
+    /// * `y (f32)`                    - The y origin of the rectangle.
+    /// * `w (f32)`                    - The width of the rectangle.
+    /// * `h (f32)`                    - The height of the rectangle.
+    /// * `z (f32)`                    - The layer of the rectangle. Lower values are drawn
+    ///   further back; with depth testing enabled (the default), a higher `z` always shows
+    ///   in front of a lower one regardless of submission order.
+    /// * `texture (TextureHandle)`    - The handle of the texture we're drawing.
+    #[allow(clippy::too_many_arguments)]
     pub fn add_tex_rect(
         &mut self,
         x: f32,
         y: f32,
         w: f32,
         h: f32,
-        texture_index: usize,
+        z: f32,
+        texture: TextureHandle,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        let (width, height) = (texture.width, texture.height);
+        self.add_tex_rect_region(
+            x, y, w, h, z, texture, 0.0, 0.0, width as f32, height as f32, flip_x, flip_y,
+        );
+    }
+
+    /// Adds a rectangle to the staging data using a sub-rectangle of a texture, in texture
+    /// pixel coordinates. This is the building block for sprite sheets/atlases: load the
+    /// whole sheet once with `load_texture`, then call this once per frame with the pixel
+    /// bounds of that frame instead of giving every sprite its own texture and bind group.
+    /// Receives:
+    /// * `x (f32)`                    - The x origin of the rectangle.
+    /// * `y (f32)`                    - The y origin of the rectangle.
+    /// * `w (f32)`                    - The width of the rectangle.
+    /// * `h (f32)`                    - The height of the rectangle.
+    /// * `z (f32)`                    - The layer of the rectangle (see `add_tex_rect`).
+    /// * `texture (TextureHandle)`    - The handle of the texture we're drawing.
+    /// * `sx (f32)`                   - The x origin of the source region, in texture pixels.
+    /// * `sy (f32)`                   - The y origin of the source region, in texture pixels.
+    /// * `sw (f32)`                   - The width of the source region, in texture pixels.
+    /// * `sh (f32)`                   - The height of the source region, in texture pixels.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_tex_rect_region(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        z: f32,
+        texture: TextureHandle,
+        sx: f32,
+        sy: f32,
+        sw: f32,
+        sh: f32,
         flip_x: bool,
         flip_y: bool,
     ) {
         // Add the object to the drawable objects vector
         let low_bound = self.staging_indices.len() as u32;
+        let instance_range = self.push_identity_instance();
         self.drawable_objects.push(DrawableObj {
-            indices_low_pos: low_bound,
-            indices_hi_pos: low_bound + 6,
-            bind_group_idx: Some(texture_index),
+            source: DrawSource::Staging {
+                indices_low_pos: low_bound,
+                indices_hi_pos: low_bound + 6,
+            },
+            bind_group_idx: Some(texture.index),
+            instance_range,
+            z,
+            phase: self.current_phase,
         });
 
         let mut tex_coords = [
-            0.0, // left
-            1.0, // right
-            0.0, // top
-            1.0, // botom
+            sx / texture.width as f32,          // left
+            (sx + sw) / texture.width as f32,   // right
+            sy / texture.height as f32,         // top
+            (sy + sh) / texture.height as f32,  // bottom
         ];
 
         if flip_x {
-            tex_coords[0] = 1.0;
-            tex_coords[1] = 0.0;
+            tex_coords.swap(0, 1);
         }
 
         if flip_y {
-            tex_coords[2] = 1.0;
-            tex_coords[3] = 0.0;
+            tex_coords.swap(2, 3);
         }
 
         self.add_staging_indexed_vertices(
             &[
                 Vertex {
-                    position: [x, y, 0.0],
+                    position: [x, y, z],
                     text_coords: [tex_coords[0], tex_coords[2]],
                     ..Default::default()
                 },
                 Vertex {
-                    position: [x, y - h, 0.0],
+                    position: [x, y - h, z],
                     text_coords: [tex_coords[0], tex_coords[3]],
                     ..Default::default()
                 },
                 Vertex {
-                    position: [x + w, y - h, 0.0],
+                    position: [x + w, y - h, z],
                     text_coords: [tex_coords[1], tex_coords[3]],
                     ..Default::default()
                 },
                 Vertex {
-                    position: [x + w, y, 0.0],
+                    position: [x + w, y, z],
                     text_coords: [tex_coords[1], tex_coords[2]],
                     ..Default::default()
                 },
@@ -725,35 +1705,42 @@ impl FrugInstance {
     /// * `y (f32)`             - The y origin of the rectangle.
     /// * `w (f32)`             - The width of the rectangle.
     /// * `h (f32)`             - The height of the rectangle.
+    /// * `z (f32)`             - The layer of the rectangle (see `add_tex_rect`).
     /// * `color [f32; 3]`      - The [red, green, blue] definition of the color to use.
-    pub fn add_colored_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: [f32; 3]) {
+    pub fn add_colored_rect(&mut self, x: f32, y: f32, w: f32, h: f32, z: f32, color: [f32; 3]) {
         // Add the object to the drawable objects vector
         let low_bound = self.staging_indices.len() as u32;
+        let instance_range = self.push_identity_instance();
         self.drawable_objects.push(DrawableObj {
-            indices_low_pos: low_bound,
-            indices_hi_pos: low_bound + 6,
+            source: DrawSource::Staging {
+                indices_low_pos: low_bound,
+                indices_hi_pos: low_bound + 6,
+            },
             bind_group_idx: None,
+            instance_range,
+            z,
+            phase: self.current_phase,
         });
 
         self.add_staging_indexed_vertices(
             &[
                 Vertex {
-                    position: [x, y, 0.0],
+                    position: [x, y, z],
                     color,
                     ..Default::default()
                 },
                 Vertex {
-                    position: [x, y - h, 0.0],
+                    position: [x, y - h, z],
                     color,
                     ..Default::default()
                 },
                 Vertex {
-                    position: [x + w, y - h, 0.0],
+                    position: [x + w, y - h, z],
                     color,
                     ..Default::default()
                 },
                 Vertex {
-                    position: [x + w, y, 0.0],
+                    position: [x + w, y, z],
                     color,
                     ..Default::default()
                 },
@@ -762,42 +1749,232 @@ impl FrugInstance {
         );
     }
 
-    /// Loads a texture
-    pub fn load_texture(&mut self, img_bytes: &[u8]) -> usize {
-        let diffuse_texture =
-            texture::Texture::from_bytes(&self.device, &self.queue, img_bytes, "texture").unwrap();
+    /// Loads a texture, returning a handle to it.
+    pub fn load_texture(&mut self, img_bytes: &[u8]) -> TextureHandle {
+        self.texture_pool.load(&self.device, &self.queue, img_bytes)
+    }
+
+    /// Loads a GPU block-compressed texture (a KTX2 or DDS container holding BC1/BC3/BC7,
+    /// ETC2, or ASTC 4x4 data) straight to the GPU, without re-encoding through the normal
+    /// image decoder - much less VRAM for large tile atlases than `load_texture`.
+    ///
+    /// If `img_bytes` isn't a recognized KTX2/DDS container at all, this falls back to
+    /// `load_texture`'s ordinary decode path (which panics if the bytes can't be decoded as
+    /// an image). If it *is* one, this
+    /// returns an error instead of panicking when the container is malformed, or when its
+    /// format isn't supported by the active backend (see
+    /// [`Self::supports_compressed_texture`]) - e.g. shipping an ASTC/BC7 atlas to a machine
+    /// whose GPU lacks that feature - so callers can degrade gracefully instead of crashing.
+    pub fn load_texture_compressed(&mut self, img_bytes: &[u8]) -> anyhow::Result<TextureHandle> {
+        match compressed_texture::parse(img_bytes) {
+            None => Ok(self.load_texture(img_bytes)),
+            Some(Ok(image))
+                if self
+                    .compressed_texture_features
+                    .contains(image.format.required_feature()) =>
+            {
+                let texture =
+                    Texture::from_compressed(&self.device, &self.queue, &image, Some("texture"));
+                Ok(self.texture_pool.insert(&self.device, texture))
+            }
+            Some(Ok(image)) => Err(anyhow::anyhow!(
+                "load_texture_compressed: {:?} isn't supported by this backend's active features",
+                image.format
+            )),
+            Some(Err(err)) => Err(err),
+        }
+    }
+
+    /// Reports whether the active backend can sample the given compressed texture format,
+    /// i.e. whether `load_texture_compressed` can upload it directly instead of falling back.
+    pub fn supports_compressed_texture(&self, format: CompressedFormat) -> bool {
+        self.compressed_texture_features
+            .contains(format.required_feature())
+    }
 
-        let diffuse_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("diffuse_bind_group"),
-            layout: &self.texture_bind_group_layout,
+    /// Loads a diffuse/normal texture pair for use with the normal-mapped lit pipeline,
+    /// returning a handle to it. See `add_lit_tex_rect`.
+    pub fn load_lit_texture(
+        &mut self,
+        diffuse_bytes: &[u8],
+        normal_bytes: &[u8],
+    ) -> LitTextureHandle {
+        let diffuse =
+            Texture::from_bytes(&self.device, &self.queue, diffuse_bytes, "lit diffuse texture")
+                .unwrap();
+        let normal =
+            Texture::from_bytes(&self.device, &self.queue, normal_bytes, "lit normal texture")
+                .unwrap();
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("lit_bind_group"),
+            layout: &self.lit_texture_bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                    resource: wgpu::BindingResource::TextureView(&diffuse.view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                    resource: wgpu::BindingResource::Sampler(&diffuse.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&normal.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&normal.sampler),
                 },
             ],
         });
 
-        self.diffuse_bind_groups.push(diffuse_bind_group);
+        self.lit_bind_groups.push(bind_group);
+        LitTextureHandle(self.lit_bind_groups.len() - 1)
+    }
+
+    /// Sets the ambient light color added to every lit sprite regardless of light distance.
+    pub fn set_ambient_light(&mut self, color: [f32; 3]) {
+        self.ambient_light = color;
+    }
+
+    /// Adds a point light affecting lit sprites drawn this frame (see `add_lit_tex_rect`).
+    ///
+    /// Only the first `MAX_LIGHTS` lights added in a frame are used.
+    pub fn add_light(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    /// Adds a rectangle to the staging data using a normal-mapped lit texture (see
+    /// `load_lit_texture`). Lighting is computed per-pixel from the active ambient color,
+    /// `add_light` lights, and the rectangle's normal map.
+    /// Receives:
+    /// * `x (f32)`                       - The x origin of the rectangle.
+    /// * `y (f32)`                       - The y origin of the rectangle.
+    /// * `w (f32)`                       - The width of the rectangle.
+    /// * `h (f32)`                       - The height of the rectangle.
+    /// * `texture (LitTextureHandle)`    - The handle of the lit texture we're drawing.
+    /// * `flip_x (bool)`                 - Whether to flip the rectangle horizontally.
+    /// * `flip_y (bool)`                 - Whether to flip the rectangle vertically.
+    pub fn add_lit_tex_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        texture: LitTextureHandle,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        let low_bound = self.staging_indices.len() as u32;
+        let instance_range = self.push_identity_instance();
+        self.lit_drawable_objects.push(LitDrawableObj {
+            indices_low_pos: low_bound,
+            indices_hi_pos: low_bound + 6,
+            bind_group_idx: texture.0,
+            instance_range,
+        });
+
+        let mut tex_coords = [
+            0.0, // left
+            1.0, // right
+            0.0, // top
+            1.0, // botom
+        ];
+
+        if flip_x {
+            tex_coords[0] = 1.0;
+            tex_coords[1] = 0.0;
+        }
+
+        if flip_y {
+            tex_coords[2] = 1.0;
+            tex_coords[3] = 0.0;
+        }
+
+        self.add_staging_indexed_vertices(
+            &[
+                Vertex {
+                    position: [x, y, 0.0],
+                    text_coords: [tex_coords[0], tex_coords[2]],
+                    ..Default::default()
+                },
+                Vertex {
+                    position: [x, y - h, 0.0],
+                    text_coords: [tex_coords[0], tex_coords[3]],
+                    ..Default::default()
+                },
+                Vertex {
+                    position: [x + w, y - h, 0.0],
+                    text_coords: [tex_coords[1], tex_coords[3]],
+                    ..Default::default()
+                },
+                Vertex {
+                    position: [x + w, y, 0.0],
+                    text_coords: [tex_coords[1], tex_coords[2]],
+                    ..Default::default()
+                },
+            ],
+            &[0, 1, 3, 1, 2, 3],
+        );
+    }
+
+    /// Uploads a mesh's vertices and indices once, returning a handle to it.
+    ///
+    /// Unlike `add_colored_vertices`/`add_tex_rect`, meshes uploaded this way keep their
+    /// GPU buffers between frames, so drawing them with `draw_mesh` doesn't require
+    /// re-pushing the geometry into the staging buffers every frame.
+    pub fn add_mesh(&mut self, vertices: &[Vertex], indices: &[u32]) -> MeshHandle {
+        self.mesh_pool.upload(&self.device, vertices, indices)
+    }
+
+    /// Draws a pooled mesh (see `add_mesh`) with the given transform and optional texture.
+    pub fn draw_mesh(
+        &mut self,
+        mesh: MeshHandle,
+        texture: Option<TextureHandle>,
+        transform: [[f32; 4]; 4],
+    ) {
+        let instance_low = self.staging_instances.len() as u32;
+        self.staging_instances.push(
+            Instance {
+                model: transform,
+                color_tint: None,
+            }
+            .to_raw(),
+        );
 
-        return self.diffuse_bind_groups.len() - 1;
+        self.drawable_objects.push(DrawableObj {
+            source: DrawSource::Mesh(mesh),
+            bind_group_idx: texture.map(|handle| handle.index),
+            instance_range: instance_low..instance_low + 1,
+            z: transform[3][2],
+            phase: self.current_phase,
+        });
     }
 
-    /// Starts running the loop
-    pub fn run<F: 'static + FnMut(&mut FrugInstance, &InputHelper)>(
+    /// Starts running the loop.
+    ///
+    /// `update_function` is called with the instance, the input helper, and the delta time
+    /// (in seconds) since the previous call. In the default mode that delta is the real,
+    /// variable time between frames; in fixed-timestep mode (see [`Self::set_fixed_timestep`])
+    /// it is always exactly the configured step, and the closure may run several times - or
+    /// not at all - per rendered frame.
+    pub fn run<F: 'static + FnMut(&mut FrugInstance, &InputHelper, f32)>(
         mut self,
         event_loop: EventLoop<()>,
         mut update_function: F,
     ) {
         let mut input = winit_input_helper::WinitInputHelper::new();
+        let mut last_frame = std::time::Instant::now();
 
         // Run the loop
         event_loop.run(move |event, _, control_flow| {
-            *control_flow = ControlFlow::Wait;
+            *control_flow = if self.fixed_timestep.is_some() {
+                ControlFlow::Poll
+            } else {
+                ControlFlow::Wait
+            };
 
             input.update(&event);
 
@@ -840,7 +2017,21 @@ impl FrugInstance {
                 Event::MainEventsCleared => {
                     self.window.request_redraw();
 
-                    update_function(&mut self, &input);
+                    let now = std::time::Instant::now();
+                    let frame_time = now.duration_since(last_frame).as_secs_f32();
+                    last_frame = now;
+
+                    if let Some(dt) = self.fixed_timestep {
+                        // Clamp the accumulator so a stalled frame (e.g. a breakpoint, a
+                        // dropped window) can't force a burst of catch-up ticks afterwards.
+                        self.timestep_accumulator += frame_time.min(MAX_ACCUMULATED_FRAME_TIME);
+                        while self.timestep_accumulator >= dt {
+                            update_function(&mut self, &input, dt);
+                            self.timestep_accumulator -= dt;
+                        }
+                    } else {
+                        update_function(&mut self, &input, frame_time);
+                    }
 
                     if self.exit_requested {
                         *control_flow = ControlFlow::Exit;
@@ -0,0 +1,58 @@
+use wgpu::util::DeviceExt;
+
+use crate::Vertex;
+
+/// A lightweight, copyable reference to a mesh owned by a [`MeshPool`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MeshHandle(pub(crate) usize);
+
+struct Mesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+/// Owns vertex/index buffers uploaded once via `add_mesh`, so geometry that doesn't change
+/// between frames doesn't need to be re-pushed through the staging buffers every frame.
+pub struct MeshPool {
+    meshes: Vec<Mesh>,
+}
+
+impl MeshPool {
+    pub(crate) fn new() -> Self {
+        Self { meshes: Vec::new() }
+    }
+
+    /// Uploads a vertex/index set once, returning a handle to it.
+    pub(crate) fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> MeshHandle {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pooled Mesh Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pooled Mesh Index Buffer"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        self.meshes.push(Mesh {
+            vertex_buffer,
+            index_buffer,
+            num_indices: indices.len() as u32,
+        });
+
+        MeshHandle(self.meshes.len() - 1)
+    }
+
+    pub(crate) fn buffers(&self, handle: MeshHandle) -> (&wgpu::Buffer, &wgpu::Buffer, u32) {
+        let mesh = &self.meshes[handle.0];
+        (&mesh.vertex_buffer, &mesh.index_buffer, mesh.num_indices)
+    }
+}
@@ -0,0 +1,564 @@
+use wgpu::util::DeviceExt;
+
+use crate::shader_preprocessor::load_shader_module;
+use crate::Vertex;
+
+/// The number of named f32 params available to a post-processing pass's shader (see
+/// `PostProcessChain::set_pass_param`). Mirrors the `MAX_LIGHTS`-style fixed-capacity
+/// uniform approach used elsewhere in the crate.
+pub const MAX_POST_PASS_PARAMS: usize = 8;
+
+/// A lightweight, copyable reference to a pass added via `PostProcessChain::add_pass`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PostPassHandle(pub(crate) usize);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostPassParams {
+    values: [f32; MAX_POST_PASS_PARAMS],
+}
+
+struct PostPass {
+    pipeline: wgpu::RenderPipeline,
+    scale: f32,
+    filter: wgpu::FilterMode,
+    address_mode: wgpu::AddressMode,
+    param_names: Vec<String>,
+    params: PostPassParams,
+    param_buffer: wgpu::Buffer,
+    param_bind_group: wgpu::BindGroup,
+    output_texture: wgpu::Texture,
+    output_view: wgpu::TextureView,
+    /// Samples whichever stage feeds this pass (the scene, or the previous pass's output).
+    /// Rebuilt whenever the chain is resized or a pass is added, since the stage it samples
+    /// may itself be recreated.
+    input_bind_group: wgpu::BindGroup,
+}
+
+/// Renders the scene to an offscreen color target, then runs a user-configurable chain of
+/// full-screen shader passes over it before the result reaches the swapchain surface -
+/// e.g. CRT filters, bloom, or color grading layered on top of the existing 2D renderer.
+///
+/// Passes are plain WGSL fragment shaders. Each one is compiled against a fixed contract:
+/// the source image is bound at `@group(0) @binding(0)` (`texture_2d<f32>`) with its sampler
+/// at `@binding(1)`, and up to [`MAX_POST_PASS_PARAMS`] user-set f32 params are available as
+/// `array<vec4<f32>, 2>` (param `i` is `params[i / 4][i % 4]`) in a uniform block at
+/// `@group(1) @binding(0)` - plain `array<f32, 8>` doesn't work here, since WGSL requires
+/// uniform-address-space array elements to be 16-byte aligned, which would make the block
+/// four times the size this crate actually uploads. The entry point must be named `fs_main`,
+/// matching every other shader in this crate.
+pub struct PostProcessChain {
+    format: wgpu::TextureFormat,
+    input_bind_group_layout: wgpu::BindGroupLayout,
+    params_bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    vertex_shader: wgpu::ShaderModule,
+    blit_pipeline: wgpu::RenderPipeline,
+    blit_param_bind_group: wgpu::BindGroup,
+    blit_bind_group: wgpu::BindGroup,
+    fullscreen_vertex_buffer: wgpu::Buffer,
+    fullscreen_index_buffer: wgpu::Buffer,
+    scene_texture: wgpu::Texture,
+    scene_view: wgpu::TextureView,
+    base_size: (u32, u32),
+    passes: Vec<PostPass>,
+}
+
+impl PostProcessChain {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> Self {
+        let vertex_shader = load_shader_module(
+            device,
+            "shader_post_process.wgsl",
+            include_str!("shader_post_process.wgsl"),
+        );
+
+        let input_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("post_process_input_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let params_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("post_process_params_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Process Pipeline Layout"),
+            bind_group_layouts: &[&input_bind_group_layout, &params_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let blit_pipeline = Self::build_pipeline(
+            device,
+            "Post Process Blit Pipeline",
+            &pipeline_layout,
+            &vertex_shader,
+            &vertex_shader,
+            format,
+        );
+
+        let (fullscreen_vertex_buffer, fullscreen_index_buffer) =
+            Self::create_fullscreen_quad(device);
+
+        let (scene_texture, scene_view) =
+            Self::create_target(device, format, size, "Post Process Scene Target");
+
+        let blit_param_bind_group =
+            Self::create_zeroed_param_bind_group(device, &params_bind_group_layout);
+        let blit_bind_group = Self::create_input_bind_group(
+            device,
+            &input_bind_group_layout,
+            &scene_view,
+            wgpu::FilterMode::Linear,
+            wgpu::AddressMode::ClampToEdge,
+        );
+
+        Self {
+            format,
+            input_bind_group_layout,
+            params_bind_group_layout,
+            pipeline_layout,
+            vertex_shader,
+            blit_pipeline,
+            blit_param_bind_group,
+            blit_bind_group,
+            fullscreen_vertex_buffer,
+            fullscreen_index_buffer,
+            scene_texture,
+            scene_view,
+            base_size: size,
+            passes: Vec::new(),
+        }
+    }
+
+    fn build_pipeline(
+        device: &wgpu::Device,
+        label: &str,
+        layout: &wgpu::PipelineLayout,
+        vertex_shader: &wgpu::ShaderModule,
+        fragment_shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: vertex_shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: fragment_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    fn create_fullscreen_quad(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer) {
+        let vertices = [
+            Vertex {
+                position: [-1.0, 1.0, 0.0],
+                text_coords: [0.0, 0.0],
+                ..Default::default()
+            },
+            Vertex {
+                position: [-1.0, -1.0, 0.0],
+                text_coords: [0.0, 1.0],
+                ..Default::default()
+            },
+            Vertex {
+                position: [1.0, -1.0, 0.0],
+                text_coords: [1.0, 1.0],
+                ..Default::default()
+            },
+            Vertex {
+                position: [1.0, 1.0, 0.0],
+                text_coords: [1.0, 0.0],
+                ..Default::default()
+            },
+        ];
+        let indices: [u32; 6] = [0, 1, 3, 1, 2, 3];
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Process Fullscreen Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Process Fullscreen Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        (vertex_buffer, index_buffer)
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        label: &str,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size.0.max(1),
+                height: size.1.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    fn create_input_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        filter: wgpu::FilterMode,
+        address_mode: wgpu::AddressMode,
+    ) -> wgpu::BindGroup {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: filter,
+            ..Default::default()
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post_process_input_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        })
+    }
+
+    fn create_zeroed_param_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::BindGroup {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Process Params Buffer"),
+            contents: bytemuck::cast_slice(&[PostPassParams {
+                values: [0.0; MAX_POST_PASS_PARAMS],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post_process_params_bind_group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        })
+    }
+
+    /// Adds a full-screen shader pass to the end of the chain, returning a handle to it.
+    ///
+    /// * `shader_src`   - WGSL source for the pass's fragment shader (see the type docs for
+    ///                    the binding contract it must follow). Run through
+    ///                    [`crate::preprocess_shader`] before compiling, so `#include`/`#define`/
+    ///                    `#ifdef` directives take effect; callers needing `#include` should
+    ///                    preprocess with their own include map first and pass the flattened
+    ///                    result here, since this call site doesn't have one to offer.
+    /// * `scale`        - The pass's output resolution relative to the base surface size, so
+    ///                    e.g. a blur pass can render at half resolution.
+    /// * `filter`       - The filter used when the next pass samples this pass's output.
+    /// * `address_mode` - The wrap mode used when the next pass samples outside this pass's
+    ///                    output, e.g. for a pass that deliberately reads beyond `[0, 1]`
+    ///                    texture coordinates (distortion, screen wrap effects).
+    /// * `param_names`  - Names for this pass's per-frame f32 params, set via
+    ///                    `set_pass_param`. At most [`MAX_POST_PASS_PARAMS`] are used.
+    ///
+    /// The last pass in the chain renders straight to the surface (see `run`), so its
+    /// `scale`, `filter` and `address_mode` have no effect.
+    pub(crate) fn add_pass(
+        &mut self,
+        device: &wgpu::Device,
+        shader_src: &str,
+        scale: f32,
+        filter: wgpu::FilterMode,
+        address_mode: wgpu::AddressMode,
+        param_names: &[&str],
+    ) -> PostPassHandle {
+        let fragment_shader = load_shader_module(device, "Post Process Pass Shader", shader_src);
+
+        let pipeline = Self::build_pipeline(
+            device,
+            "Post Process Pass Pipeline",
+            &self.pipeline_layout,
+            &self.vertex_shader,
+            &fragment_shader,
+            self.format,
+        );
+
+        let params = PostPassParams {
+            values: [0.0; MAX_POST_PASS_PARAMS],
+        };
+        let param_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Process Pass Params Buffer"),
+            contents: bytemuck::cast_slice(&[params]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let param_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post_process_pass_params_bind_group"),
+            layout: &self.params_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: param_buffer.as_entire_binding(),
+            }],
+        });
+
+        let output_size = Self::scaled_size(self.base_size, scale);
+        let (output_texture, output_view) =
+            Self::create_target(device, self.format, output_size, "Post Process Pass Target");
+
+        // Bound properly by `rebuild_bind_groups` below; this placeholder keeps the struct
+        // fully initialized in the meantime.
+        let input_bind_group = Self::create_input_bind_group(
+            device,
+            &self.input_bind_group_layout,
+            &self.scene_view,
+            filter,
+            address_mode,
+        );
+
+        self.passes.push(PostPass {
+            pipeline,
+            scale,
+            filter,
+            address_mode,
+            param_names: param_names.iter().map(|name| name.to_string()).collect(),
+            params,
+            param_buffer,
+            param_bind_group,
+            output_texture,
+            output_view,
+            input_bind_group,
+        });
+
+        let handle = PostPassHandle(self.passes.len() - 1);
+        self.rebuild_bind_groups(device);
+        handle
+    }
+
+    /// Sets a named per-frame param on a pass, uploaded to its uniform buffer next `run`.
+    pub(crate) fn set_pass_param(&mut self, pass: PostPassHandle, name: &str, value: f32) {
+        let pass = &mut self.passes[pass.0];
+        if let Some(index) = pass.param_names.iter().position(|n| n == name) {
+            pass.params.values[index] = value;
+        }
+    }
+
+    fn scaled_size(base_size: (u32, u32), scale: f32) -> (u32, u32) {
+        (
+            ((base_size.0 as f32) * scale).round().max(1.0) as u32,
+            ((base_size.1 as f32) * scale).round().max(1.0) as u32,
+        )
+    }
+
+    /// Recreates the scene target and every pass's output texture for a new base size (e.g.
+    /// on window resize), then rebinds the chain so each stage samples the right source.
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, size: (u32, u32)) {
+        self.base_size = size;
+
+        let (scene_texture, scene_view) =
+            Self::create_target(device, self.format, size, "Post Process Scene Target");
+        self.scene_texture = scene_texture;
+        self.scene_view = scene_view;
+
+        for pass in &mut self.passes {
+            let output_size = Self::scaled_size(self.base_size, pass.scale);
+            let (output_texture, output_view) = Self::create_target(
+                device,
+                self.format,
+                output_size,
+                "Post Process Pass Target",
+            );
+            pass.output_texture = output_texture;
+            pass.output_view = output_view;
+        }
+
+        self.rebuild_bind_groups(device);
+    }
+
+    /// Rebuilds every "sample the previous stage" bind group, since the previous stage's
+    /// output texture (or the scene texture) may have just been recreated.
+    fn rebuild_bind_groups(&mut self, device: &wgpu::Device) {
+        let mut input_bind_groups = Vec::with_capacity(self.passes.len());
+
+        let mut prev_view = &self.scene_view;
+        let mut prev_filter = wgpu::FilterMode::Linear;
+        let mut prev_address_mode = wgpu::AddressMode::ClampToEdge;
+        for pass in &self.passes {
+            input_bind_groups.push(Self::create_input_bind_group(
+                device,
+                &self.input_bind_group_layout,
+                prev_view,
+                prev_filter,
+                prev_address_mode,
+            ));
+            prev_view = &pass.output_view;
+            prev_filter = pass.filter;
+            prev_address_mode = pass.address_mode;
+        }
+        let blit_bind_group = Self::create_input_bind_group(
+            device,
+            &self.input_bind_group_layout,
+            prev_view,
+            prev_filter,
+            prev_address_mode,
+        );
+
+        for (pass, bind_group) in self.passes.iter_mut().zip(input_bind_groups) {
+            pass.input_bind_group = bind_group;
+        }
+        self.blit_bind_group = blit_bind_group;
+    }
+
+    /// The offscreen target the main scene should be rendered into.
+    pub(crate) fn scene_view(&self) -> &wgpu::TextureView {
+        &self.scene_view
+    }
+
+    /// Runs the pass chain: the scene (as rendered into `scene_view`) flows through every
+    /// user pass in order, and the final stage (the last pass, or an implicit blit if there
+    /// are none) writes to `surface_view`.
+    pub(crate) fn run(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        surface_view: &wgpu::TextureView,
+    ) {
+        let pass_count = self.passes.len();
+        for (i, pass) in self.passes.iter_mut().enumerate() {
+            queue.write_buffer(&pass.param_buffer, 0, bytemuck::cast_slice(&[pass.params]));
+
+            let target = if i + 1 == pass_count {
+                surface_view
+            } else {
+                &pass.output_view
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Process Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&pass.pipeline);
+            render_pass.set_bind_group(0, &pass.input_bind_group, &[]);
+            render_pass.set_bind_group(1, &pass.param_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.fullscreen_vertex_buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.fullscreen_index_buffer.slice(..),
+                wgpu::IndexFormat::Uint32,
+            );
+            render_pass.draw_indexed(0..6, 0, 0..1);
+        }
+
+        if pass_count == 0 {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Process Blit"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: surface_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&self.blit_pipeline);
+            render_pass.set_bind_group(0, &self.blit_bind_group, &[]);
+            render_pass.set_bind_group(1, &self.blit_param_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.fullscreen_vertex_buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.fullscreen_index_buffer.slice(..),
+                wgpu::IndexFormat::Uint32,
+            );
+            render_pass.draw_indexed(0..6, 0, 0..1);
+        }
+    }
+}
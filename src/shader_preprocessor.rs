@@ -0,0 +1,197 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// An error produced while flattening a shader's `#include`/`#define`/`#ifdef` directives.
+#[derive(Debug)]
+pub enum PreprocessError {
+    /// `#include "path"` referenced a path not present in the include map.
+    MissingInclude(String),
+    /// `#include "path"` directly or transitively includes itself.
+    IncludeCycle(Vec<String>),
+    /// `#endif` with no matching `#ifdef`/`#ifndef`.
+    UnmatchedEndif,
+    /// One or more `#ifdef`/`#ifndef` blocks were never closed with `#endif`.
+    UnmatchedIfdef,
+    /// A directive line couldn't be parsed, e.g. `#include` missing its quoted path.
+    MalformedDirective(String),
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingInclude(path) => write!(f, "unresolved #include \"{path}\""),
+            Self::IncludeCycle(stack) => write!(f, "cyclic #include: {}", stack.join(" -> ")),
+            Self::UnmatchedEndif => write!(f, "#endif with no matching #ifdef/#ifndef"),
+            Self::UnmatchedIfdef => write!(f, "#ifdef/#ifndef without a matching #endif"),
+            Self::MalformedDirective(line) => {
+                write!(f, "malformed preprocessor directive: {line}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {}
+
+struct Context<'a> {
+    includes: &'a HashMap<String, String>,
+    defines: HashSet<String>,
+    substitutions: Vec<(String, String)>,
+    include_stack: Vec<String>,
+}
+
+/// Flattens `#include "path"`, `#define NAME [value]`, and `#ifdef`/`#ifndef`/`#endif`
+/// directives out of a WGSL source string, so the crate (and its users) can share reusable
+/// fragments - e.g. a camera transform or sampling helper - across otherwise-independent
+/// shaders instead of duplicating them.
+///
+/// * `source`   - The root shader source.
+/// * `includes` - A virtual filesystem mapping `#include "path"` paths to their contents.
+///                Includes are resolved recursively, with cycles rejected.
+/// * `defines`  - The set of names considered active for `#ifdef`/`#ifndef` at the start of
+///                `source`. `#define NAME` directives found while processing add to this set
+///                for the remainder of that scope; `#define NAME value` additionally
+///                substitutes whole-word occurrences of `NAME` with `value` in the output.
+pub fn preprocess_shader(
+    source: &str,
+    includes: &HashMap<String, String>,
+    defines: &HashSet<String>,
+) -> Result<String, PreprocessError> {
+    let mut ctx = Context {
+        includes,
+        defines: defines.clone(),
+        substitutions: Vec::new(),
+        include_stack: Vec::new(),
+    };
+    process(source, &mut ctx)
+}
+
+fn process(source: &str, ctx: &mut Context) -> Result<String, PreprocessError> {
+    let mut output = String::new();
+    // One entry per currently-open `#ifdef`/`#ifndef`, `true` if that block's own condition
+    // (and every ancestor block's) is active.
+    let mut block_stack: Vec<bool> = Vec::new();
+
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        let parent_active = block_stack.iter().all(|active| *active);
+
+        if let Some(rest) = trimmed.strip_prefix("#include") {
+            if !parent_active {
+                continue;
+            }
+            let path = parse_quoted(rest)
+                .ok_or_else(|| PreprocessError::MalformedDirective(line.to_string()))?;
+            if ctx.include_stack.contains(&path) {
+                let mut cycle = ctx.include_stack.clone();
+                cycle.push(path);
+                return Err(PreprocessError::IncludeCycle(cycle));
+            }
+            let body = ctx
+                .includes
+                .get(&path)
+                .ok_or_else(|| PreprocessError::MissingInclude(path.clone()))?
+                .clone();
+
+            ctx.include_stack.push(path);
+            let expanded = process(&body, ctx)?;
+            ctx.include_stack.pop();
+
+            output.push_str(&expanded);
+        } else if let Some(rest) = trimmed.strip_prefix("#ifndef") {
+            let name = rest.trim().to_string();
+            block_stack.push(parent_active && !ctx.defines.contains(&name));
+        } else if let Some(rest) = trimmed.strip_prefix("#ifdef") {
+            let name = rest.trim().to_string();
+            block_stack.push(parent_active && ctx.defines.contains(&name));
+        } else if trimmed.starts_with("#endif") {
+            if block_stack.pop().is_none() {
+                return Err(PreprocessError::UnmatchedEndif);
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("#define") {
+            if !parent_active {
+                continue;
+            }
+            let rest = rest.trim();
+            let (name, value) = match rest.split_once(char::is_whitespace) {
+                Some((name, value)) => (name.to_string(), value.trim().to_string()),
+                None => (rest.to_string(), String::new()),
+            };
+            ctx.defines.insert(name.clone());
+            if !value.is_empty() {
+                ctx.substitutions.push((name, value));
+            }
+        } else if parent_active {
+            output.push_str(&substitute(line, &ctx.substitutions));
+            output.push('\n');
+        }
+    }
+
+    if !block_stack.is_empty() {
+        return Err(PreprocessError::UnmatchedIfdef);
+    }
+
+    Ok(output)
+}
+
+/// Parses the `"path"` out of an `#include "path"` directive's trailing text.
+fn parse_quoted(rest: &str) -> Option<String> {
+    let rest = rest.trim().strip_prefix('"')?;
+    let end = rest.find('"')?;
+    Some(rest[..end].to_string())
+}
+
+fn substitute(line: &str, substitutions: &[(String, String)]) -> String {
+    let mut line = line.to_string();
+    for (name, value) in substitutions {
+        line = replace_token(&line, name, value);
+    }
+    line
+}
+
+/// Replaces whole-word occurrences of `name` with `value`, so e.g. `#define N 4` doesn't
+/// clobber `MAX_N` or `N2`.
+fn replace_token(line: &str, name: &str, value: &str) -> String {
+    let bytes = line.as_bytes();
+    let mut result = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < line.len() {
+        if line[i..].starts_with(name) {
+            let before_ok = i == 0 || !is_ident_byte(bytes[i - 1]);
+            let after = i + name.len();
+            let after_ok = after >= bytes.len() || !is_ident_byte(bytes[after]);
+            if before_ok && after_ok {
+                result.push_str(value);
+                i = after;
+                continue;
+            }
+        }
+
+        let ch = line[i..].chars().next().unwrap();
+        result.push(ch);
+        i += ch.len_utf8();
+    }
+
+    result
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Runs `source` through [`preprocess_shader`] (with no includes/defines of its own - callers
+/// needing those should preprocess beforehand and pass the result here) and compiles it,
+/// so every shader module this crate creates - built-in or user-supplied - shares the same
+/// `#include`/`#define`/`#ifdef` support instead of only some call sites having it wired up.
+pub(crate) fn load_shader_module(
+    device: &wgpu::Device,
+    label: &str,
+    source: &str,
+) -> wgpu::ShaderModule {
+    let processed = preprocess_shader(source, &HashMap::new(), &HashSet::new())
+        .unwrap_or_else(|err| panic!("failed to preprocess shader \"{label}\": {err}"));
+    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(processed.into()),
+    })
+}
@@ -0,0 +1,69 @@
+use crate::texture::Texture;
+
+/// A lightweight, copyable reference to a texture owned by a [`TexturePool`], carrying the
+/// texture's pixel dimensions so callers can slice it into sub-regions (see
+/// `FrugInstance::add_tex_rect_region`) without a separate size query.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureHandle {
+    pub(crate) index: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Owns the GPU textures and bind groups loaded via `load_texture`, handing back
+/// [`TextureHandle`]s so callers don't need to manage bind groups themselves.
+pub struct TexturePool {
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl TexturePool {
+    pub(crate) fn new(bind_group_layout: wgpu::BindGroupLayout) -> Self {
+        Self {
+            bind_group_layout,
+            bind_groups: Vec::new(),
+        }
+    }
+
+    /// Decodes and uploads a texture, returning a handle to it.
+    pub(crate) fn load(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img_bytes: &[u8],
+    ) -> TextureHandle {
+        let diffuse_texture = Texture::from_bytes(device, queue, img_bytes, "texture").unwrap();
+        self.insert(device, diffuse_texture)
+    }
+
+    /// Adds an already-uploaded texture (e.g. a block-compressed one), returning a handle.
+    pub(crate) fn insert(&mut self, device: &wgpu::Device, texture: Texture) -> TextureHandle {
+        let (width, height) = (texture.width, texture.height);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("diffuse_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                },
+            ],
+        });
+
+        self.bind_groups.push(bind_group);
+        TextureHandle {
+            index: self.bind_groups.len() - 1,
+            width,
+            height,
+        }
+    }
+
+    pub(crate) fn bind_group_by_index(&self, index: usize) -> &wgpu::BindGroup {
+        &self.bind_groups[index]
+    }
+}